@@ -16,15 +16,23 @@
 //! - [Elf](elf/struct.Elf.html): elf image loader.
 //! - [BzImage](bzimage/struct.BzImage.html): bzImage loader.
 //! - [PE](pe/struct.PE.html): PE image loader.
+//! - [Multiboot](struct.Multiboot.html): Multiboot/Multiboot2 image loader.
+//! - [load_kernel](fn.load_kernel.html): detect the kernel image format and load it with the
+//!   matching loader.
+//! - [load_volatile](fn.load_volatile.html): zero-copy counterpart of the loaders above, reading
+//!   straight from a descriptor into guest memory; used by
+//!   [Multiboot::load_volatile](struct.Multiboot.html#method.load_volatile).
+//! - [load_cmdline_checked](fn.load_cmdline_checked.html): like [load_cmdline](fn.load_cmdline.html),
+//!   but reports the number of bytes required and can enforce a maximum command line length.
 
 extern crate vm_memory;
 
 use std::fmt;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom};
 
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 use vm_memory::ByteValued;
-use vm_memory::{Address, Bytes, GuestAddress, GuestMemory, GuestUsize};
+use vm_memory::{Address, Bytes, GuestAddress, GuestMemory, GuestUsize, ReadVolatile};
 
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 pub use crate::loader_gen::bootparam;
@@ -41,6 +49,11 @@ mod aarch64;
 #[cfg(target_arch = "aarch64")]
 pub use aarch64::*;
 
+#[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+mod multiboot;
+#[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+pub use multiboot::Multiboot;
+
 #[derive(Debug, PartialEq)]
 /// Kernel loader errors.
 pub enum Error {
@@ -56,6 +69,10 @@ pub enum Error {
     #[cfg(all(feature = "pe", target_arch = "aarch64"))]
     Pe(pe::Error),
 
+    /// Failed to load Multiboot image.
+    #[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+    Multiboot(multiboot::Error),
+
     /// Failed writing command line to guest memory.
     CommandLineCopy,
     /// Command line overflowed guest memory.
@@ -64,6 +81,8 @@ pub enum Error {
     InvalidKernelStartAddress,
     /// Memory to load kernel image is too small.
     MemoryOverflow,
+    /// The kernel image format could not be identified.
+    UnknownImageFormat,
 }
 
 /// A specialized [`Result`] type for the kernel loader.
@@ -80,11 +99,14 @@ impl fmt::Display for Error {
             Error::Elf(ref _e) => "failed to load ELF kernel image",
             #[cfg(all(feature = "pe", target_arch = "aarch64"))]
             Error::Pe(ref _e) => "failed to load PE kernel image",
+            #[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+            Error::Multiboot(ref _e) => "failed to load Multiboot kernel image",
 
             Error::CommandLineCopy => "failed writing command line to guest memory",
             Error::CommandLineOverflow => "command line overflowed guest memory",
             Error::InvalidKernelStartAddress => "invalid kernel start address",
             Error::MemoryOverflow => "memory to load kernel image is not enough",
+            Error::UnknownImageFormat => "unknown kernel image format",
         };
 
         write!(f, "Kernel Loader: {}", desc)
@@ -100,11 +122,14 @@ impl std::error::Error for Error {
             Error::Elf(ref e) => Some(e),
             #[cfg(all(feature = "pe", target_arch = "aarch64"))]
             Error::Pe(ref e) => Some(e),
+            #[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+            Error::Multiboot(ref e) => Some(e),
 
             Error::CommandLineCopy => None,
             Error::CommandLineOverflow => None,
             Error::InvalidKernelStartAddress => None,
             Error::MemoryOverflow => None,
+            Error::UnknownImageFormat => None,
         }
     }
 }
@@ -130,6 +155,13 @@ impl From<pe::Error> for Error {
     }
 }
 
+#[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+impl From<multiboot::Error> for Error {
+    fn from(err: multiboot::Error) -> Self {
+        Error::Multiboot(err)
+    }
+}
+
 /// Result of [`KernelLoader.load()`](trait.KernelLoader.html#tymethod.load).
 ///
 /// This specifies where the kernel is loading and passes additional
@@ -151,6 +183,10 @@ pub struct KernelLoaderResult {
     /// https://xenbits.xen.org/docs/unstable/misc/pvh.html
     #[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
     pub pvh_boot_cap: elf::PvhBootCapability,
+    /// Kernel entry point, for formats such as Multiboot that specify it explicitly in the
+    /// image header rather than via a boot protocol-specific structure.
+    #[cfg(all(feature = "multiboot", any(target_arch = "x86", target_arch = "x86_64")))]
+    pub kernel_entry: Option<GuestAddress>,
 }
 
 /// Trait that specifies kernel image loading support.
@@ -180,6 +216,146 @@ unsafe impl ByteValued for bootparam::setup_header {}
 #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
 unsafe impl ByteValued for bootparam::boot_params {}
 
+/// Peeks `buf.len()` bytes from `kernel_image` at `offset`, then rewinds the reader back to
+/// wherever it was positioned before the call.
+#[cfg(any(
+    all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "pe", target_arch = "aarch64")
+))]
+fn peek<F: Read + Seek>(kernel_image: &mut F, offset: u64, buf: &mut [u8]) -> Result<()> {
+    let current = kernel_image
+        .stream_position()
+        .map_err(|_| Error::UnknownImageFormat)?;
+    kernel_image
+        .seek(SeekFrom::Start(offset))
+        .map_err(|_| Error::UnknownImageFormat)?;
+    let result = kernel_image.read_exact(buf);
+    kernel_image
+        .seek(SeekFrom::Start(current))
+        .map_err(|_| Error::UnknownImageFormat)?;
+    result.map_err(|_| Error::UnknownImageFormat)
+}
+
+/// ELF images start with the 4-byte magic `0x7F 'E' 'L' 'F'` at offset 0.
+#[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
+fn is_elf<F: Read + Seek>(kernel_image: &mut F) -> bool {
+    let mut magic = [0u8; 4];
+    peek(kernel_image, 0, &mut magic).is_ok() && magic == [0x7f, b'E', b'L', b'F']
+}
+
+/// x86 bzImages carry the boot flag `0xAA55` at offset 510 and the header magic `"HdrS"` at
+/// offset 0x202.
+#[cfg(all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")))]
+fn is_bzimage<F: Read + Seek>(kernel_image: &mut F) -> bool {
+    let mut boot_flag = [0u8; 2];
+    let mut header_magic = [0u8; 4];
+    peek(kernel_image, 510, &mut boot_flag).is_ok()
+        && u16::from_le_bytes(boot_flag) == 0xaa55
+        && peek(kernel_image, 0x202, &mut header_magic).is_ok()
+        && u32::from_le_bytes(header_magic) == 0x5372_6448
+}
+
+/// aarch64 PE/`Image` kernels carry the DOS magic `"MZ"` at offset 0 and the arm64 magic
+/// `ARM\x64` at offset 56.
+#[cfg(all(feature = "pe", target_arch = "aarch64"))]
+fn is_pe<F: Read + Seek>(kernel_image: &mut F) -> bool {
+    let mut dos_magic = [0u8; 2];
+    let mut arm64_magic = [0u8; 4];
+    peek(kernel_image, 0, &mut dos_magic).is_ok()
+        && &dos_magic == b"MZ"
+        && peek(kernel_image, 56, &mut arm64_magic).is_ok()
+        && u32::from_le_bytes(arm64_magic) == 0x644d_5241
+}
+
+/// Identifies the format of `kernel_image` from its header and loads it with the matching
+/// [`KernelLoader`] implementation, so VMMs don't need to know in advance whether they are
+/// booting an ELF, a bzImage, or (on aarch64) a PE/`Image` kernel.
+///
+/// The image is probed non-destructively: a handful of bytes are read from well-known offsets
+/// and the reader is rewound to its original position before the matching loader runs, mirroring
+/// the ELF -> bzImage fallback crosvm performs today.
+///
+/// # Arguments
+///
+/// * `guest_mem`: [`GuestMemory`] to load the kernel in.
+/// * `kernel_offset`: Usage varies between implementations.
+/// * `kernel_image`: Kernel image to be loaded.
+/// * `highmem_start_address`: Address where high memory starts.
+///
+/// [`GuestMemory`]: https://docs.rs/vm-memory/latest/vm_memory/guest_memory/trait.GuestMemory.html
+#[cfg(any(
+    all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")),
+    all(feature = "pe", target_arch = "aarch64")
+))]
+pub fn load_kernel<F, M: GuestMemory>(
+    guest_mem: &M,
+    kernel_offset: Option<GuestAddress>,
+    kernel_image: &mut F,
+    highmem_start_address: Option<GuestAddress>,
+) -> Result<KernelLoaderResult>
+where
+    F: Read + Seek,
+{
+    #[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
+    if is_elf(kernel_image) {
+        return elf::Elf::load(guest_mem, kernel_offset, kernel_image, highmem_start_address)
+            .map_err(Error::from);
+    }
+
+    #[cfg(all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")))]
+    if is_bzimage(kernel_image) {
+        return bzimage::BzImage::load(
+            guest_mem,
+            kernel_offset,
+            kernel_image,
+            highmem_start_address,
+        )
+        .map_err(Error::from);
+    }
+
+    #[cfg(all(feature = "pe", target_arch = "aarch64"))]
+    if is_pe(kernel_image) {
+        return pe::PE::load(guest_mem, kernel_offset, kernel_image, highmem_start_address)
+            .map_err(Error::from);
+    }
+
+    Err(Error::UnknownImageFormat)
+}
+
+/// Reads `count` bytes from `src`, starting at its current position, directly into `guest_mem`
+/// at `guest_addr`, using volatile accesses instead of staging the data through an intermediate
+/// host buffer.
+///
+/// This is the zero-copy counterpart of the buffered reads the individual format loaders perform
+/// via [`Read`]: `src` only needs to support [`ReadVolatile`], so a descriptor-backed reader
+/// (e.g. `vm-memory`'s `FileReadWriteAtVolatile`, seeked to the desired file offset) can hand
+/// bytes straight to the guest's mapped memory, without an intermediate `Vec` copy.
+///
+/// # Arguments
+///
+/// * `guest_mem`: [`GuestMemory`] to load the segment into.
+/// * `guest_addr`: Address in `guest_mem` at which to load the segment.
+/// * `src`: Source of the segment's bytes.
+/// * `count`: Number of bytes to copy.
+///
+/// [`GuestMemory`]: https://docs.rs/vm-memory/latest/vm_memory/guest_memory/trait.GuestMemory.html
+/// [`ReadVolatile`]: https://docs.rs/vm-memory/latest/vm_memory/trait.ReadVolatile.html
+pub fn load_volatile<F, M: GuestMemory>(
+    guest_mem: &M,
+    guest_addr: GuestAddress,
+    src: &mut F,
+    count: usize,
+) -> Result<()>
+where
+    F: ReadVolatile,
+{
+    guest_mem
+        .read_exact_volatile_from(guest_addr, src, count)
+        .map_err(|_| Error::MemoryOverflow)
+}
+
 /// Writes the command line string to the given guest memory slice.
 ///
 /// # Arguments
@@ -212,14 +388,53 @@ pub fn load_cmdline<M: GuestMemory>(
     guest_addr: GuestAddress,
     cmdline: &Cmdline,
 ) -> Result<()> {
+    load_cmdline_checked(guest_mem, guest_addr, cmdline, None).map(|_| ())
+}
+
+/// Writes the command line string to the given guest memory slice, reporting the number of
+/// bytes it required and optionally enforcing a maximum length before writing anything.
+///
+/// # Arguments
+///
+/// * `guest_mem` - [`GuestMemory`] that will be partially overwritten by the command line.
+/// * `guest_addr` - The address in `guest_mem` at which to load the command line.
+/// * `cmdline` - The kernel command line.
+/// * `max_cmdline_len` - Optional upper bound, in bytes and including the NUL terminator, on the
+///   command line length, e.g. the x86_64 boot protocol's `cmdline_size` advertised in the
+///   loaded `setup_header`. `None` means only the bounds of `guest_mem` are enforced.
+///
+/// # Returns
+///
+/// The number of bytes `cmdline` required, including the NUL terminator, so the caller can
+/// pre-reserve space for it. Returns `0` for an empty command line, which is not written at all.
+///
+/// [`GuestMemory`]: https://docs.rs/vm-memory/latest/vm_memory/guest_memory/trait.GuestMemory.html
+pub fn load_cmdline_checked<M: GuestMemory>(
+    guest_mem: &M,
+    guest_addr: GuestAddress,
+    cmdline: &Cmdline,
+    max_cmdline_len: Option<u32>,
+) -> Result<u32> {
     let len = cmdline.as_str().len();
     if len == 0 {
-        return Ok(());
+        return Ok(0);
+    }
+
+    // Extra for null termination.
+    let required_len = u32::try_from(len)
+        .map_err(|_| Error::CommandLineOverflow)?
+        .checked_add(1)
+        .ok_or(Error::CommandLineOverflow)?;
+
+    if let Some(max_len) = max_cmdline_len {
+        if required_len > max_len {
+            return Err(Error::CommandLineOverflow);
+        }
     }
 
     let end = guest_addr
-        .checked_add(len as u64 + 1)
-        .ok_or(Error::CommandLineOverflow)?; // Extra for null termination.
+        .checked_add(u64::from(required_len))
+        .ok_or(Error::CommandLineOverflow)?;
     if end > guest_mem.last_addr() {
         return Err(Error::CommandLineOverflow);
     }
@@ -228,7 +443,7 @@ pub fn load_cmdline<M: GuestMemory>(
         .write_slice(cmdline.as_str().as_bytes(), guest_addr)
         .map_err(|_| Error::CommandLineCopy)?;
 
-    Ok(())
+    Ok(required_len)
 }
 
 #[cfg(test)]
@@ -243,6 +458,109 @@ mod tests {
         GuestMemoryMmap::from_ranges(&[(GuestAddress(0x0), (MEM_SIZE as usize))]).unwrap()
     }
 
+    #[test]
+    #[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
+    fn test_is_elf_matches_and_rewinds() {
+        let mut cursor = std::io::Cursor::new(vec![0x7f, b'E', b'L', b'F', 1, 2, 3, 4]);
+        cursor.seek(SeekFrom::Start(2)).unwrap();
+        assert!(is_elf(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 2);
+    }
+
+    #[test]
+    #[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
+    fn test_is_elf_no_match_rewinds() {
+        let mut cursor = std::io::Cursor::new(vec![0u8; 8]);
+        cursor.seek(SeekFrom::Start(3)).unwrap();
+        assert!(!is_elf(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 3);
+    }
+
+    #[test]
+    #[cfg(all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")))]
+    fn test_is_bzimage_matches_and_rewinds() {
+        let mut image = vec![0u8; 0x206];
+        image[510] = 0x55;
+        image[511] = 0xaa;
+        image[0x202..0x206].copy_from_slice(&0x5372_6448u32.to_le_bytes());
+        let mut cursor = std::io::Cursor::new(image);
+        cursor.seek(SeekFrom::Start(5)).unwrap();
+        assert!(is_bzimage(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 5);
+    }
+
+    #[test]
+    #[cfg(all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")))]
+    fn test_is_bzimage_no_match_rewinds() {
+        let mut cursor = std::io::Cursor::new(vec![0u8; 0x206]);
+        cursor.seek(SeekFrom::Start(5)).unwrap();
+        assert!(!is_bzimage(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 5);
+    }
+
+    #[test]
+    #[cfg(all(feature = "pe", target_arch = "aarch64"))]
+    fn test_is_pe_matches_and_rewinds() {
+        let mut image = vec![0u8; 64];
+        image[0] = b'M';
+        image[1] = b'Z';
+        image[56..60].copy_from_slice(&0x644d_5241u32.to_le_bytes());
+        let mut cursor = std::io::Cursor::new(image);
+        cursor.seek(SeekFrom::Start(4)).unwrap();
+        assert!(is_pe(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 4);
+    }
+
+    #[test]
+    #[cfg(all(feature = "pe", target_arch = "aarch64"))]
+    fn test_is_pe_no_match_rewinds() {
+        let mut cursor = std::io::Cursor::new(vec![0u8; 64]);
+        cursor.seek(SeekFrom::Start(4)).unwrap();
+        assert!(!is_pe(&mut cursor));
+        assert_eq!(cursor.stream_position().unwrap(), 4);
+    }
+
+    #[test]
+    #[cfg(any(
+        all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")),
+        all(feature = "bzimage", any(target_arch = "x86", target_arch = "x86_64")),
+        all(feature = "pe", target_arch = "aarch64")
+    ))]
+    fn test_load_kernel_unknown_format() {
+        let gm = create_guest_mem();
+        let mut cursor = std::io::Cursor::new(vec![0u8; 4096]);
+        assert_eq!(
+            Err(Error::UnknownImageFormat),
+            load_kernel(&gm, None, &mut cursor, None)
+        );
+    }
+
+    #[test]
+    fn test_load_volatile() {
+        let gm = create_guest_mem();
+        let data = b"zero-copy volatile payload".to_vec();
+        let mut src = std::io::Cursor::new(data.clone());
+        let guest_addr = GuestAddress(0x1000);
+
+        assert_eq!(Ok(()), load_volatile(&gm, guest_addr, &mut src, data.len()));
+
+        let mut read_back = vec![0u8; data.len()];
+        gm.read_slice(&mut read_back, guest_addr).unwrap();
+        assert_eq!(read_back, data);
+    }
+
+    #[test]
+    fn test_load_volatile_overflow() {
+        let gm = create_guest_mem();
+        let mut src = std::io::Cursor::new(vec![0u8; 16]);
+        let guest_addr = GuestAddress(MEM_SIZE - 4);
+
+        assert_eq!(
+            Err(Error::MemoryOverflow),
+            load_volatile(&gm, guest_addr, &mut src, 16)
+        );
+    }
+
     #[test]
     fn test_cmdline_overflow() {
         let gm = create_guest_mem();
@@ -277,4 +595,34 @@ mod tests {
         let val: u8 = gm.read_obj(cmdline_address).unwrap();
         assert_eq!(val, '\0' as u8);
     }
+
+    #[test]
+    fn test_cmdline_checked_required_len() {
+        let gm = create_guest_mem();
+        let mut cl = Cmdline::new(10);
+        cl.insert_str("1234").unwrap();
+        assert_eq!(
+            Ok(0),
+            load_cmdline_checked(&gm, GuestAddress(0), &Cmdline::new(10), None)
+        );
+        assert_eq!(
+            Ok(5),
+            load_cmdline_checked(&gm, GuestAddress(0), &cl, None)
+        );
+    }
+
+    #[test]
+    fn test_cmdline_checked_max_len() {
+        let gm = create_guest_mem();
+        let mut cl = Cmdline::new(10);
+        cl.insert_str("1234").unwrap();
+        assert_eq!(
+            Err(Error::CommandLineOverflow),
+            load_cmdline_checked(&gm, GuestAddress(0), &cl, Some(4))
+        );
+        assert_eq!(
+            Ok(5),
+            load_cmdline_checked(&gm, GuestAddress(0), &cl, Some(5))
+        );
+    }
 }
@@ -0,0 +1,643 @@
+// Copyright 2024 The linux-loader Authors. All rights reserved.
+//
+// SPDX-License-Identifier: Apache-2.0 AND BSD-3-Clause
+
+//! Loader for Multiboot/Multiboot2 compliant kernel images.
+//!
+//! See the [Multiboot Specification](https://www.gnu.org/software/grub/manual/multiboot/multiboot.html)
+//! for details of the header format parsed here.
+//!
+//! A Multiboot2 header, or a Multiboot1 header without valid address fields, is delegated to the
+//! ELF loader, since the image is then expected to be a plain ELF kernel. That fallback requires
+//! the `elf` feature; without it, those images fail to load with
+//! [`Error::ElfFallbackUnavailable`].
+
+use std::fmt;
+use std::io::{Read, Seek, SeekFrom};
+
+use vm_memory::{Bytes, GuestAddress, GuestMemory};
+
+use super::{Error as KernelLoaderError, KernelLoader, KernelLoaderResult};
+
+/// Multiboot1 magic number.
+const MULTIBOOT1_MAGIC: u32 = 0x1bad_b002;
+/// Multiboot2 magic number.
+const MULTIBOOT2_MAGIC: u32 = 0xe852_50d6;
+/// Number of bytes scanned from the start of the image while looking for a Multiboot header.
+const SEARCH_WINDOW: usize = 8192;
+
+/// Multiboot loader errors.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Unable to read the kernel image.
+    ReadKernelImage,
+    /// Unable to seek the kernel image.
+    SeekKernelImage,
+    /// No Multiboot magic found within the first 8KiB of the kernel image.
+    MagicNotFound,
+    /// The Multiboot1 header checksum does not match (`magic + flags + checksum != 0`).
+    InvalidChecksum,
+    /// The header's address fields are inconsistent with each other or with the kernel image.
+    InvalidHeader,
+    /// The image has no valid Multiboot1 address fields and must be loaded as a plain ELF
+    /// kernel, but the `elf` feature is not enabled.
+    ElfFallbackUnavailable,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let desc = match self {
+            Error::ReadKernelImage => "unable to read kernel image",
+            Error::SeekKernelImage => "unable to seek kernel image",
+            Error::MagicNotFound => "no Multiboot magic found in kernel image",
+            Error::InvalidChecksum => "invalid Multiboot header checksum",
+            Error::InvalidHeader => "invalid Multiboot header address fields",
+            Error::ElfFallbackUnavailable => {
+                "image requires the ELF loader fallback, but the \"elf\" feature is not enabled"
+            }
+        };
+        write!(f, "Multiboot Kernel Loader: {}", desc)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Loader for Multiboot/Multiboot2 compliant kernel images.
+#[derive(Debug)]
+pub struct Multiboot;
+
+/// Decoded fields of a Multiboot1 header.
+struct Multiboot1Header {
+    magic: u32,
+    flags: u32,
+    checksum: u32,
+    header_addr: u32,
+    load_addr: u32,
+    load_end_addr: u32,
+    bss_end_addr: u32,
+    entry_addr: u32,
+}
+
+impl Multiboot1Header {
+    /// Size, in bytes, of the fixed Multiboot1 header fields.
+    const SIZE: usize = 32;
+    /// Flag bit (`AOUT_KLUDGE`) indicating that `header_addr`..`entry_addr` are valid.
+    const ADDRESS_FIELDS_FLAG: u32 = 1 << 16;
+
+    fn parse(bytes: &[u8; Self::SIZE]) -> Self {
+        let word = |i: usize| {
+            u32::from(bytes[i * 4])
+                | u32::from(bytes[i * 4 + 1]) << 8
+                | u32::from(bytes[i * 4 + 2]) << 16
+                | u32::from(bytes[i * 4 + 3]) << 24
+        };
+
+        Multiboot1Header {
+            magic: word(0),
+            flags: word(1),
+            checksum: word(2),
+            header_addr: word(3),
+            load_addr: word(4),
+            load_end_addr: word(5),
+            bss_end_addr: word(6),
+            entry_addr: word(7),
+        }
+    }
+
+    fn checksum_valid(&self) -> bool {
+        self.magic
+            .wrapping_add(self.flags)
+            .wrapping_add(self.checksum)
+            == 0
+    }
+
+    fn has_address_fields(&self) -> bool {
+        self.flags & Self::ADDRESS_FIELDS_FLAG != 0
+    }
+}
+
+/// Scans the first [`SEARCH_WINDOW`] bytes of `kernel_image` on 4-byte boundaries for a
+/// Multiboot1 or Multiboot2 magic number, leaving the reader rewound to its original position.
+///
+/// Returns the byte offset of the magic within the image together with the magic value found.
+fn find_header<F: Read + Seek>(kernel_image: &mut F) -> Result<(u64, u32), Error> {
+    let current = kernel_image
+        .stream_position()
+        .map_err(|_| Error::SeekKernelImage)?;
+    kernel_image
+        .seek(SeekFrom::Start(0))
+        .map_err(|_| Error::SeekKernelImage)?;
+
+    let mut window = [0u8; SEARCH_WINDOW];
+    let mut filled = 0;
+    while filled < window.len() {
+        match kernel_image.read(&mut window[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err(Error::ReadKernelImage),
+        }
+    }
+
+    kernel_image
+        .seek(SeekFrom::Start(current))
+        .map_err(|_| Error::SeekKernelImage)?;
+
+    window[..filled]
+        .chunks_exact(4)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let magic = u32::from(chunk[0])
+                | u32::from(chunk[1]) << 8
+                | u32::from(chunk[2]) << 16
+                | u32::from(chunk[3]) << 24;
+            (i as u64 * 4, magic)
+        })
+        .find(|&(_, magic)| magic == MULTIBOOT1_MAGIC || magic == MULTIBOOT2_MAGIC)
+        .ok_or(Error::MagicNotFound)
+}
+
+/// Falls back to loading `kernel_image` as a plain ELF kernel, for Multiboot2 images and
+/// Multiboot1 images without valid address fields.
+///
+/// The `elf` module, like the rest of this crate's loaders, is optional: enabling `multiboot`
+/// without `elf` must not fail to compile, so this reference is cfg-gated the same way
+/// `Error::Elf`/`From<elf::Error>`/`is_elf` are in `mod.rs`.
+#[cfg(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64")))]
+fn load_as_elf<F, M: GuestMemory>(
+    guest_mem: &M,
+    kernel_offset: Option<GuestAddress>,
+    kernel_image: &mut F,
+    highmem_start_address: Option<GuestAddress>,
+) -> super::Result<KernelLoaderResult>
+where
+    F: Read + Seek,
+{
+    super::elf::Elf::load(guest_mem, kernel_offset, kernel_image, highmem_start_address)
+}
+
+#[cfg(not(all(feature = "elf", any(target_arch = "x86", target_arch = "x86_64"))))]
+fn load_as_elf<F, M: GuestMemory>(
+    _guest_mem: &M,
+    _kernel_offset: Option<GuestAddress>,
+    _kernel_image: &mut F,
+    _highmem_start_address: Option<GuestAddress>,
+) -> super::Result<KernelLoaderResult>
+where
+    F: Read + Seek,
+{
+    Err(KernelLoaderError::Multiboot(Error::ElfFallbackUnavailable))
+}
+
+/// Resolved placement of a Multiboot1 segment, validated against `guest_mem`.
+struct Layout {
+    /// Offset of the segment's first byte within the kernel image file.
+    file_start: u64,
+    /// Address in `guest_mem` to copy the segment to.
+    guest_addr: GuestAddress,
+    /// Number of bytes to copy from the file.
+    load_size: u64,
+    /// Address in `guest_mem` one past the last byte copied from the file.
+    load_end: u64,
+    /// Address in `guest_mem` one past the last byte of BSS to zero (`>= load_end`).
+    kernel_end: u64,
+    /// Kernel entry point.
+    entry_addr: u64,
+}
+
+/// Parses and validates the Multiboot1 header in `kernel_image`, resolving where its segment
+/// should be read from and copied to.
+///
+/// Returns `Ok(None)`, with `kernel_image` rewound to `start_pos`, when the image should instead
+/// be treated as a plain ELF kernel (a Multiboot2 header, or a Multiboot1 header without valid
+/// address fields).
+fn resolve_layout<F: Read + Seek, M: GuestMemory>(
+    kernel_image: &mut F,
+    guest_mem: &M,
+    start_pos: u64,
+) -> super::Result<Option<Layout>> {
+    let (header_offset, magic) = find_header(kernel_image).map_err(KernelLoaderError::Multiboot)?;
+
+    if magic != MULTIBOOT1_MAGIC {
+        // Multiboot2 headers use a tag-based layout rather than the fixed fields handled below.
+        // As with a Multiboot1 header that has no address fields, fall back to treating the
+        // image as a plain ELF kernel. `find_header` already rewound the stream.
+        return Ok(None);
+    }
+
+    let mut raw_header = [0u8; Multiboot1Header::SIZE];
+    kernel_image
+        .seek(SeekFrom::Start(header_offset))
+        .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+    kernel_image
+        .read_exact(&mut raw_header)
+        .map_err(|_| KernelLoaderError::Multiboot(Error::ReadKernelImage))?;
+    let header = Multiboot1Header::parse(&raw_header);
+
+    if !header.checksum_valid() {
+        return Err(KernelLoaderError::Multiboot(Error::InvalidChecksum));
+    }
+
+    if !header.has_address_fields() {
+        kernel_image
+            .seek(SeekFrom::Start(start_pos))
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+        return Ok(None);
+    }
+
+    let header_addr = u64::from(header.header_addr);
+    let load_addr = u64::from(header.load_addr);
+    let load_end_addr = u64::from(header.load_end_addr);
+    let bss_end_addr = u64::from(header.bss_end_addr);
+    let entry_addr = u64::from(header.entry_addr);
+
+    // The header sits `header_addr - load_addr` bytes into the segment to be loaded, and we
+    // already know where the header itself lives in the file.
+    let segment_offset_in_header = header_addr
+        .checked_sub(load_addr)
+        .ok_or(KernelLoaderError::Multiboot(Error::InvalidHeader))?;
+    let file_start = header_offset
+        .checked_sub(segment_offset_in_header)
+        .ok_or(KernelLoaderError::Multiboot(Error::InvalidHeader))?;
+
+    // `load_end_addr == 0` means "load the rest of the file": resolve it against the file's
+    // actual length up front, rather than reading until `guest_mem` runs out, so an oversized
+    // image is reported as `Error::MemoryOverflow` instead of silently truncated.
+    let load_size = if load_end_addr == 0 {
+        let file_len = kernel_image
+            .seek(SeekFrom::End(0))
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+        file_len
+            .checked_sub(file_start)
+            .ok_or(KernelLoaderError::Multiboot(Error::InvalidHeader))?
+    } else {
+        load_end_addr
+            .checked_sub(load_addr)
+            .ok_or(KernelLoaderError::Multiboot(Error::InvalidHeader))?
+    };
+
+    let load_end = load_addr
+        .checked_add(load_size)
+        .ok_or(KernelLoaderError::MemoryOverflow)?;
+
+    // `bss_end_addr == 0` means there is no BSS to zero.
+    let kernel_end = if bss_end_addr != 0 {
+        bss_end_addr
+    } else {
+        load_end
+    };
+
+    if kernel_end < load_end {
+        return Err(KernelLoaderError::Multiboot(Error::InvalidHeader));
+    }
+    if GuestAddress(kernel_end) > guest_mem.last_addr() {
+        return Err(KernelLoaderError::MemoryOverflow);
+    }
+
+    Ok(Some(Layout {
+        file_start,
+        guest_addr: GuestAddress(load_addr),
+        load_size,
+        load_end,
+        kernel_end,
+        entry_addr,
+    }))
+}
+
+/// Zeroes the BSS region described by `layout`, if any.
+fn zero_bss<M: GuestMemory>(guest_mem: &M, layout: &Layout) -> super::Result<()> {
+    let bss_len = (layout.kernel_end - layout.load_end) as usize;
+    if bss_len > 0 {
+        guest_mem
+            .write_slice(&vec![0u8; bss_len], GuestAddress(layout.load_end))
+            .map_err(|_| KernelLoaderError::MemoryOverflow)?;
+    }
+    Ok(())
+}
+
+fn result_from_layout(layout: &Layout) -> KernelLoaderResult {
+    KernelLoaderResult {
+        kernel_load: layout.guest_addr,
+        kernel_end: layout.kernel_end,
+        kernel_entry: Some(GuestAddress(layout.entry_addr)),
+        ..Default::default()
+    }
+}
+
+impl KernelLoader for Multiboot {
+    /// Loads a Multiboot kernel.
+    ///
+    /// `kernel_offset` and `highmem_start_address` are ignored for Multiboot1 images with valid
+    /// address fields, which specify their own absolute load address. Images without them are
+    /// delegated to the ELF loader, which receives both arguments unchanged.
+    fn load<F, M: GuestMemory>(
+        guest_mem: &M,
+        kernel_offset: Option<GuestAddress>,
+        kernel_image: &mut F,
+        highmem_start_address: Option<GuestAddress>,
+    ) -> super::Result<KernelLoaderResult>
+    where
+        F: Read + Seek,
+    {
+        let start_pos = kernel_image
+            .stream_position()
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+
+        let layout = match resolve_layout(kernel_image, guest_mem, start_pos)? {
+            Some(layout) => layout,
+            None => {
+                return load_as_elf(guest_mem, kernel_offset, kernel_image, highmem_start_address)
+            }
+        };
+
+        kernel_image
+            .seek(SeekFrom::Start(layout.file_start))
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+        guest_mem
+            .read_exact_from(layout.guest_addr, kernel_image, layout.load_size as usize)
+            .map_err(|_| KernelLoaderError::MemoryOverflow)?;
+
+        zero_bss(guest_mem, &layout)?;
+
+        Ok(result_from_layout(&layout))
+    }
+}
+
+impl Multiboot {
+    /// Zero-copy counterpart of [`KernelLoader::load`], reading the kernel segment straight into
+    /// guest memory via [`super::load_volatile`] instead of staging it through an intermediate
+    /// host buffer.
+    ///
+    /// Only Multiboot1 images with valid address fields take this path; images delegated to the
+    /// ELF loader are loaded through its buffered [`Read`] path, as in [`KernelLoader::load`].
+    ///
+    /// This is a sibling method rather than a `KernelLoader` override because the trait's `load`
+    /// signature is fixed to `F: Read + Seek`, and can't grow the `ReadVolatile` bound this needs.
+    pub fn load_volatile<F, M: GuestMemory>(
+        guest_mem: &M,
+        kernel_offset: Option<GuestAddress>,
+        kernel_image: &mut F,
+        highmem_start_address: Option<GuestAddress>,
+    ) -> super::Result<KernelLoaderResult>
+    where
+        F: Read + Seek + vm_memory::ReadVolatile,
+    {
+        let start_pos = kernel_image
+            .stream_position()
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+
+        let layout = match resolve_layout(kernel_image, guest_mem, start_pos)? {
+            Some(layout) => layout,
+            None => {
+                return load_as_elf(guest_mem, kernel_offset, kernel_image, highmem_start_address)
+            }
+        };
+
+        kernel_image
+            .seek(SeekFrom::Start(layout.file_start))
+            .map_err(|_| KernelLoaderError::Multiboot(Error::SeekKernelImage))?;
+        super::load_volatile(
+            guest_mem,
+            layout.guest_addr,
+            kernel_image,
+            layout.load_size as usize,
+        )?;
+
+        zero_bss(guest_mem, &layout)?;
+
+        Ok(result_from_layout(&layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use vm_memory::Address;
+
+    type GuestMemoryMmap = vm_memory::GuestMemoryMmap<()>;
+
+    const MEM_SIZE: u64 = 0x10_0000;
+
+    fn create_guest_mem() -> GuestMemoryMmap {
+        GuestMemoryMmap::from_ranges(&[(GuestAddress(0x0), MEM_SIZE as usize)]).unwrap()
+    }
+
+    /// Builds the bytes of a Multiboot1 header with a correct checksum.
+    fn header_bytes(
+        flags: u32,
+        header_addr: u32,
+        load_addr: u32,
+        load_end_addr: u32,
+        bss_end_addr: u32,
+        entry_addr: u32,
+    ) -> [u8; Multiboot1Header::SIZE] {
+        let checksum = 0u32
+            .wrapping_sub(MULTIBOOT1_MAGIC)
+            .wrapping_sub(flags);
+        let words = [
+            MULTIBOOT1_MAGIC,
+            flags,
+            checksum,
+            header_addr,
+            load_addr,
+            load_end_addr,
+            bss_end_addr,
+            entry_addr,
+        ];
+
+        let mut bytes = [0u8; Multiboot1Header::SIZE];
+        for (i, word) in words.iter().enumerate() {
+            bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_checksum_valid() {
+        let header = Multiboot1Header::parse(&header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            0x1000,
+            0x1000,
+            0x1010,
+            0x1020,
+            0x1000,
+        ));
+        assert!(header.checksum_valid());
+        assert!(header.has_address_fields());
+    }
+
+    #[test]
+    fn test_checksum_invalid() {
+        let mut bytes = header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            0x1000,
+            0x1000,
+            0x1010,
+            0x1020,
+            0x1000,
+        );
+        bytes[8] ^= 0xff; // Corrupt the checksum word.
+        assert!(!Multiboot1Header::parse(&bytes).checksum_valid());
+    }
+
+    #[test]
+    fn test_find_header_multiboot1() {
+        let mut image = vec![0u8; 64];
+        image[16..20].copy_from_slice(&MULTIBOOT1_MAGIC.to_le_bytes());
+        let mut cursor = Cursor::new(image);
+        assert_eq!(find_header(&mut cursor), Ok((16, MULTIBOOT1_MAGIC)));
+    }
+
+    #[test]
+    fn test_find_header_multiboot2() {
+        let mut image = vec![0u8; 64];
+        image[32..36].copy_from_slice(&MULTIBOOT2_MAGIC.to_le_bytes());
+        let mut cursor = Cursor::new(image);
+        assert_eq!(find_header(&mut cursor), Ok((32, MULTIBOOT2_MAGIC)));
+    }
+
+    #[test]
+    fn test_find_header_not_found() {
+        let mut cursor = Cursor::new(vec![0u8; 64]);
+        assert_eq!(find_header(&mut cursor), Err(Error::MagicNotFound));
+    }
+
+    #[test]
+    fn test_load_with_address_fields() {
+        let gm = create_guest_mem();
+        let load_addr = 0x2000u32;
+        let payload = b"payload-bytes-to-copy!!";
+        let total_len = Multiboot1Header::SIZE as u32 + payload.len() as u32;
+        let load_end_addr = load_addr + total_len;
+        let bss_end_addr = load_end_addr + 16;
+        let entry_addr = load_addr + 4;
+
+        let header = header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            load_addr,
+            load_addr,
+            load_end_addr,
+            bss_end_addr,
+            entry_addr,
+        );
+        let mut image = header.to_vec();
+        image.extend_from_slice(payload);
+        let mut cursor = Cursor::new(image);
+
+        let result = Multiboot::load(&gm, None, &mut cursor, None).unwrap();
+        assert_eq!(result.kernel_load, GuestAddress(u64::from(load_addr)));
+        assert_eq!(result.kernel_end, u64::from(bss_end_addr));
+        assert_eq!(
+            result.kernel_entry,
+            Some(GuestAddress(u64::from(entry_addr)))
+        );
+
+        let mut read_back = vec![0u8; payload.len()];
+        gm.read_slice(
+            &mut read_back,
+            GuestAddress(u64::from(load_addr) + Multiboot1Header::SIZE as u64),
+        )
+        .unwrap();
+        assert_eq!(read_back.as_slice(), payload);
+    }
+
+    #[test]
+    fn test_load_volatile_with_address_fields() {
+        let gm = create_guest_mem();
+        let load_addr = 0x2000u32;
+        let payload = b"zero-copy-payload-bytes!";
+        let total_len = Multiboot1Header::SIZE as u32 + payload.len() as u32;
+        let load_end_addr = load_addr + total_len;
+        let bss_end_addr = load_end_addr + 16;
+        let entry_addr = load_addr + 4;
+
+        let header = header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            load_addr,
+            load_addr,
+            load_end_addr,
+            bss_end_addr,
+            entry_addr,
+        );
+        let mut image = header.to_vec();
+        image.extend_from_slice(payload);
+        let mut cursor = Cursor::new(image);
+
+        let result = Multiboot::load_volatile(&gm, None, &mut cursor, None).unwrap();
+        assert_eq!(result.kernel_load, GuestAddress(u64::from(load_addr)));
+        assert_eq!(result.kernel_end, u64::from(bss_end_addr));
+        assert_eq!(
+            result.kernel_entry,
+            Some(GuestAddress(u64::from(entry_addr)))
+        );
+
+        let mut read_back = vec![0u8; payload.len()];
+        gm.read_slice(
+            &mut read_back,
+            GuestAddress(u64::from(load_addr) + Multiboot1Header::SIZE as u64),
+        )
+        .unwrap();
+        assert_eq!(read_back.as_slice(), payload);
+    }
+
+    #[test]
+    fn test_load_bad_checksum() {
+        let gm = create_guest_mem();
+        let mut header = header_bytes(Multiboot1Header::ADDRESS_FIELDS_FLAG, 0, 0, 32, 32, 0);
+        header[8] ^= 0xff;
+        let mut cursor = Cursor::new(header.to_vec());
+
+        assert_eq!(
+            Multiboot::load(&gm, None, &mut cursor, None),
+            Err(KernelLoaderError::Multiboot(Error::InvalidChecksum))
+        );
+    }
+
+    #[test]
+    fn test_load_bss_before_load_end_is_invalid() {
+        let gm = create_guest_mem();
+        let load_addr = 0x1000u32;
+        let load_end_addr = load_addr + 64;
+        // bss_end_addr < load_end_addr is an invalid header, not a no-op.
+        let header = header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            load_addr,
+            load_addr,
+            load_end_addr,
+            load_addr + 4,
+            load_addr,
+        );
+        let mut image = header.to_vec();
+        image.extend_from_slice(&[0u8; 64]);
+        let mut cursor = Cursor::new(image);
+
+        assert_eq!(
+            Multiboot::load(&gm, None, &mut cursor, None),
+            Err(KernelLoaderError::Multiboot(Error::InvalidHeader))
+        );
+    }
+
+    #[test]
+    fn test_load_overflows_guest_memory() {
+        let gm = create_guest_mem();
+        let load_addr = (MEM_SIZE - 16) as u32;
+        let load_end_addr = load_addr + 256; // Past the end of guest memory.
+        let header = header_bytes(
+            Multiboot1Header::ADDRESS_FIELDS_FLAG,
+            load_addr,
+            load_addr,
+            load_end_addr,
+            load_end_addr,
+            load_addr,
+        );
+        let mut image = header.to_vec();
+        image.extend_from_slice(&[0u8; 256]);
+        let mut cursor = Cursor::new(image);
+
+        assert_eq!(
+            Multiboot::load(&gm, None, &mut cursor, None),
+            Err(KernelLoaderError::MemoryOverflow)
+        );
+    }
+}